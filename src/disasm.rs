@@ -0,0 +1,232 @@
+//! Disassembles real object files (ELF, Mach-O, PE) into the same
+//! `&str` shape the tree-sitter highlight pipeline already consumes, so the
+//! viewer can show actual binaries instead of the hardcoded ARM64 demo
+//! string.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use capstone::prelude::*;
+use object::{Object, ObjectSection, ObjectSymbol, SectionKind, SymbolKind};
+
+/// One function symbol found in the object file, exposed so a future
+/// navigation sidebar can jump `uniform_list` to its first line.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub address: u64,
+}
+
+/// The instruction set a listing was disassembled for. Its `grammar_id` is
+/// the override passed to `AssemblyViewer::open`, so the right grammar is
+/// picked even before the path-suffix matcher runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Isa {
+    X86_64,
+    Arm64,
+}
+
+impl Isa {
+    /// The tree-sitter grammar id (`LanguageConfig::grammar`) that decodes
+    /// this ISA's mnemonics, e.g. `"asm"`. Unlike a human-facing language
+    /// name, a grammar id is guaranteed to match whatever a bundled or
+    /// installed extension actually registered, since both sides read it
+    /// from the same `config.toml` field rather than a separately
+    /// maintained display string.
+    pub fn grammar_id(self) -> &'static str {
+        match self {
+            Isa::X86_64 => "asm",
+            Isa::Arm64 => "asm",
+        }
+    }
+
+    fn from_object(file: &object::File) -> Option<Self> {
+        match file.architecture() {
+            object::Architecture::X86_64 => Some(Isa::X86_64),
+            object::Architecture::Aarch64 => Some(Isa::Arm64),
+            _ => None,
+        }
+    }
+
+    fn build_capstone(self) -> capstone::Result<Capstone> {
+        let mut cs = match self {
+            Isa::X86_64 => Capstone::new().x86().mode(arch::x86::ArchMode::Mode64).build()?,
+            Isa::Arm64 => Capstone::new().arm64().mode(arch::arm64::ArchMode::Arm).build()?,
+        };
+        cs.set_detail(true)?;
+        Ok(cs)
+    }
+}
+
+/// A disassembled object file: highlight-ready text, the ISA it was
+/// decoded with, and the symbols found, in address order.
+pub struct Listing {
+    pub text: String,
+    pub isa: Isa,
+    pub symbols: Vec<Symbol>,
+}
+
+#[derive(Debug)]
+pub enum DisassembleError {
+    Io(std::io::Error),
+    Object(object::Error),
+    Capstone(capstone::Error),
+    UnsupportedIsa(object::Architecture),
+}
+
+impl fmt::Display for DisassembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisassembleError::Io(err) => write!(f, "{err}"),
+            DisassembleError::Object(err) => write!(f, "{err}"),
+            DisassembleError::Capstone(err) => write!(f, "{err}"),
+            DisassembleError::UnsupportedIsa(arch) => write!(f, "unsupported architecture {arch:?}"),
+        }
+    }
+}
+
+impl std::error::Error for DisassembleError {}
+
+impl From<std::io::Error> for DisassembleError {
+    fn from(err: std::io::Error) -> Self {
+        DisassembleError::Io(err)
+    }
+}
+
+impl From<object::Error> for DisassembleError {
+    fn from(err: object::Error) -> Self {
+        DisassembleError::Object(err)
+    }
+}
+
+/// Reads `path` and disassembles it; see `disassemble_bytes`.
+pub fn disassemble_file(path: &Path) -> Result<Listing, DisassembleError> {
+    let bytes = std::fs::read(path)?;
+    disassemble_bytes(&bytes)
+}
+
+/// Parses `bytes` as an ELF, Mach-O, or PE object file, locates its
+/// executable sections and symbols, and emits one listing with an address
+/// column, symbol-derived label lines, and branch-target comments.
+pub fn disassemble_bytes(bytes: &[u8]) -> Result<Listing, DisassembleError> {
+    let file = object::File::parse(bytes)?;
+    let isa = Isa::from_object(&file).ok_or_else(|| DisassembleError::UnsupportedIsa(file.architecture()))?;
+    let cs = isa.build_capstone().map_err(DisassembleError::Capstone)?;
+
+    let symbol_names: BTreeMap<u64, String> = file
+        .symbols()
+        .filter(|sym| sym.kind() == SymbolKind::Text && sym.address() != 0)
+        .map(|sym| (sym.address(), sym.name().unwrap_or("?").to_string()))
+        .collect();
+
+    let mut text = String::new();
+    for section in file.sections() {
+        if section.kind() != SectionKind::Text {
+            continue;
+        }
+        let Ok(data) = section.data() else { continue };
+        let insns = cs
+            .disasm_all(data, section.address())
+            .map_err(DisassembleError::Capstone)?;
+
+        for insn in insns.iter() {
+            if let Some(name) = symbol_names.get(&insn.address()) {
+                text.push_str(&format!("{name}:\n"));
+            }
+
+            let comment = branch_target(&cs, insn)
+                .and_then(|target| symbol_names.get(&target))
+                .map(|name| format!("  // -> {name}"))
+                .unwrap_or_default();
+
+            // `{:<8}` only pads up to 8 columns; it doesn't truncate or
+            // force a gap, so an explicit space after it is still required
+            // for mnemonics that are 8+ characters wide (`vpcmpeqb`, etc.)
+            // to not run straight into their operands.
+            text.push_str(&format!(
+                "    {:08x}:  {:<8} {}{}\n",
+                insn.address(),
+                insn.mnemonic().unwrap_or(""),
+                insn.op_str().unwrap_or(""),
+                comment,
+            ));
+        }
+    }
+
+    let symbols = symbol_names.into_iter().map(|(address, name)| Symbol { name, address }).collect();
+    Ok(Listing { text, isa, symbols })
+}
+
+/// Reads a call/branch instruction's immediate operand, if it has one, as
+/// the candidate address for a `// -> symbol` comment. Non-control-flow
+/// instructions (e.g. `mov w0, #0x0`) are skipped outright, since their
+/// immediates aren't addresses and could collide with an unrelated symbol.
+fn branch_target(cs: &Capstone, insn: &capstone::Insn) -> Option<u64> {
+    let detail = cs.insn_detail(insn).ok()?;
+    if !is_call_or_jump(&detail) {
+        return None;
+    }
+    detail.arch_detail().operands().into_iter().find_map(|op| match op {
+        arch::ArchOperand::X86Operand(op) => match op.op_type {
+            arch::x86::X86OperandType::Imm(imm) => Some(imm as u64),
+            _ => None,
+        },
+        arch::ArchOperand::Arm64Operand(op) => match op.op_type {
+            arch::arm64::Arm64OperandType::Imm(imm) => Some(imm as u64),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Whether `detail`'s instruction groups include an unconditional/
+/// conditional jump or a call, i.e. an instruction whose immediate operand
+/// (if any) is actually an address rather than incidental data.
+fn is_call_or_jump(detail: &capstone::InsnDetail) -> bool {
+    detail.groups().iter().any(|group| {
+        matches!(
+            group.0 as u32,
+            x if x == capstone::InsnGroupType::CS_GRP_JUMP as u32
+                || x == capstone::InsnGroupType::CS_GRP_CALL as u32
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branch_target_reads_x86_call_immediate() {
+        let cs = Isa::X86_64.build_capstone().unwrap();
+        let insns = cs.disasm_all(&[0xe8, 0x00, 0x00, 0x00, 0x00], 0x1000).unwrap();
+        let insn = insns.iter().next().unwrap();
+        assert_eq!(branch_target(&cs, insn), Some(0x1005));
+    }
+
+    #[test]
+    fn branch_target_ignores_x86_mov_immediate() {
+        let cs = Isa::X86_64.build_capstone().unwrap();
+        let insns = cs.disasm_all(&[0xb8, 0x00, 0x10, 0x00, 0x00], 0x1000).unwrap();
+        let insn = insns.iter().next().unwrap();
+        assert_eq!(branch_target(&cs, insn), None);
+    }
+
+    #[test]
+    fn branch_target_reads_arm64_bl_immediate() {
+        let cs = Isa::Arm64.build_capstone().unwrap();
+        let insns = cs.disasm_all(&[0x01, 0x00, 0x00, 0x94], 0x1000).unwrap();
+        let insn = insns.iter().next().unwrap();
+        assert_eq!(branch_target(&cs, insn), Some(0x1004));
+    }
+
+    #[test]
+    fn branch_target_ignores_arm64_mov_immediate() {
+        let cs = Isa::Arm64.build_capstone().unwrap();
+        let insns = cs.disasm_all(&[0x00, 0x00, 0x80, 0x52], 0x1000).unwrap();
+        let insn = insns.iter().next().unwrap();
+        assert_eq!(branch_target(&cs, insn), None);
+    }
+
+}
@@ -0,0 +1,288 @@
+//! Loads extensions — grammars, languages, and themes — from an
+//! `extensions/installed/<name>/` directory tree, mirroring the layout
+//! editor extensions use, so new ISAs or color schemes can be added without
+//! a recompile.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use languages::{LanguageConfig, LanguageQueries, LanguageRegistry, LoadedLanguage};
+use serde::Deserialize;
+
+use crate::language_select::LanguageSelector;
+use crate::theme::ThemeRegistry;
+
+/// `extensions/installed/<name>/manifest.json`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtensionManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub languages: Vec<LanguageEntry>,
+}
+
+/// One `languages/<dir>` entry declared by a manifest.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LanguageEntry {
+    /// Directory name under `languages/`, e.g. `"mips"`.
+    pub dir: String,
+    /// Grammar id; the loader expects `grammars/<grammar>.so` to export a
+    /// `tree_sitter_<grammar>` symbol.
+    pub grammar: String,
+}
+
+#[derive(Debug)]
+pub enum ExtensionError {
+    Io(std::io::Error),
+    Manifest { path: PathBuf, source: serde_json::Error },
+    Config { path: PathBuf, source: toml::de::Error },
+    Grammar { path: PathBuf, source: libloading::Error },
+    NotInstalled(String),
+}
+
+impl fmt::Display for ExtensionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtensionError::Io(err) => write!(f, "{err}"),
+            ExtensionError::Manifest { path, source } => {
+                write!(f, "failed to parse manifest {}: {source}", path.display())
+            }
+            ExtensionError::Config { path, source } => {
+                write!(f, "failed to parse language config {}: {source}", path.display())
+            }
+            ExtensionError::Grammar { path, source } => {
+                write!(f, "failed to load grammar {}: {source}", path.display())
+            }
+            ExtensionError::NotInstalled(name) => write!(f, "extension {name:?} is not installed"),
+        }
+    }
+}
+
+impl std::error::Error for ExtensionError {}
+
+impl From<std::io::Error> for ExtensionError {
+    fn from(err: std::io::Error) -> Self {
+        ExtensionError::Io(err)
+    }
+}
+
+/// Enumerates installed extensions and registers each one's grammars,
+/// languages, and themes into the shared registries, and supports
+/// installing, reloading, and uninstalling extensions at runtime.
+pub struct ExtensionStore {
+    root: PathBuf,
+    languages: Arc<LanguageRegistry>,
+    themes: Arc<ThemeRegistry>,
+    selector: Arc<LanguageSelector>,
+}
+
+impl ExtensionStore {
+    pub fn new(
+        root: impl Into<PathBuf>,
+        languages: Arc<LanguageRegistry>,
+        themes: Arc<ThemeRegistry>,
+        selector: Arc<LanguageSelector>,
+    ) -> Self {
+        Self { root: root.into(), languages, themes, selector }
+    }
+
+    /// Loads every extension under `root`. Called once at startup; a
+    /// malformed extension is logged and skipped rather than aborting the
+    /// whole scan.
+    pub fn load_installed(&self) -> Result<(), ExtensionError> {
+        if !self.root.is_dir() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(&self.root)? {
+            let dir = entry?.path();
+            if !dir.is_dir() {
+                continue;
+            }
+            if let Err(err) = self.load_extension(&dir) {
+                eprintln!("failed to load extension {}: {err}", dir.display());
+            }
+        }
+        Ok(())
+    }
+
+    fn load_extension(&self, dir: &Path) -> Result<(), ExtensionError> {
+        let manifest_path = dir.join("manifest.json");
+        let manifest: ExtensionManifest = serde_json::from_str(&fs::read_to_string(&manifest_path)?)
+            .map_err(|source| ExtensionError::Manifest { path: manifest_path.clone(), source })?;
+
+        for entry in &manifest.languages {
+            self.register_language(dir, entry)?;
+        }
+
+        let themes_dir = dir.join("themes");
+        if themes_dir.is_dir() {
+            if let Err(err) = self.themes.load_dir(&themes_dir) {
+                eprintln!("failed to load themes for extension {}: {err}", manifest.id);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn register_language(&self, ext_dir: &Path, entry: &LanguageEntry) -> Result<(), ExtensionError> {
+        let grammar_path = ext_dir.join("grammars").join(format!("{}.so", entry.grammar));
+        let symbol = format!("tree_sitter_{}", entry.grammar);
+
+        // SAFETY: extensions are only ever loaded from `root`, which is
+        // populated exclusively through `install`.
+        let language = unsafe {
+            let lib = libloading::Library::new(&grammar_path)
+                .map_err(|source| ExtensionError::Grammar { path: grammar_path.clone(), source })?;
+            let get: libloading::Symbol<unsafe extern "C" fn() -> tree_sitter::Language> = lib
+                .get(symbol.as_bytes())
+                .map_err(|source| ExtensionError::Grammar { path: grammar_path.clone(), source })?;
+            let language = get();
+            std::mem::forget(lib); // kept resident for the process's lifetime
+            language
+        };
+        self.languages.register_native_grammars([(entry.grammar.as_str(), language)]);
+
+        let lang_dir = ext_dir.join("languages").join(&entry.dir);
+        let config_path = lang_dir.join("config.toml");
+        let config: LanguageConfig = toml::from_str(&fs::read_to_string(&config_path)?)
+            .map_err(|source| ExtensionError::Config { path: config_path, source })?;
+
+        // Queries, like the grammar library above, are kept resident for the
+        // process's lifetime rather than reloaded per lookup, matching
+        // `LanguageQueries`'s `Option<&'static str>` fields.
+        let mut queries = LanguageQueries::default();
+        queries.highlight = read_query_leaked(&lang_dir.join("highlight.scm"));
+        queries.injections = read_query_leaked(&lang_dir.join("injections.scm"));
+        queries.locals = read_query_leaked(&lang_dir.join("locals.scm"));
+
+        // Registers with the registry and the selector together, so the
+        // two can't drift out of sync.
+        let loaded_config = config.clone();
+        self.selector.register_language(&self.languages, config, move || LoadedLanguage {
+            config: loaded_config.clone(),
+            queries: queries.clone(),
+            context_provider: None,
+            toolchain_provider: None,
+        });
+        Ok(())
+    }
+
+    /// Re-reads one installed extension's manifest, grammars, languages,
+    /// and themes from disk, e.g. after `install` drops a new version in
+    /// place.
+    pub fn reload(&self, id: &str) -> Result<(), ExtensionError> {
+        let dir = self.root.join(id);
+        if !dir.is_dir() {
+            return Err(ExtensionError::NotInstalled(id.to_string()));
+        }
+        self.load_extension(&dir)
+    }
+
+    /// Copies an extension directory into `root` and loads it immediately.
+    pub fn install(&self, source_dir: &Path) -> Result<(), ExtensionError> {
+        let manifest_path = source_dir.join("manifest.json");
+        let manifest: ExtensionManifest = serde_json::from_str(&fs::read_to_string(&manifest_path)?)
+            .map_err(|source| ExtensionError::Manifest { path: manifest_path.clone(), source })?;
+
+        let dest = self.root.join(&manifest.id);
+        copy_dir_recursive(source_dir, &dest)?;
+        self.load_extension(&dest)
+    }
+
+    /// Removes an installed extension from disk. Already-registered
+    /// languages and themes stay active until the app restarts, since
+    /// `LanguageRegistry` has no unregister path.
+    pub fn uninstall(&self, id: &str) -> Result<(), ExtensionError> {
+        let dir = self.root.join(id);
+        if !dir.is_dir() {
+            return Err(ExtensionError::NotInstalled(id.to_string()));
+        }
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
+
+/// Reads a query file and leaks it to `&'static str`, since it's loaded at
+/// most once per extension load and needs to outlive the closures that
+/// capture it in `register_language`.
+fn read_query_leaked(path: &Path) -> Option<&'static str> {
+    fs::read_to_string(path).ok().map(|s| &*Box::leak(s.into_boxed_str()))
+}
+
+fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let dest_path = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_recursive(&entry.path(), &dest_path)?;
+        } else {
+            fs::copy(entry.path(), &dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_parses_with_languages() {
+        let manifest: ExtensionManifest = serde_json::from_str(
+            r#"{"id": "mips", "name": "MIPS", "version": "0.1.0", "languages": [{"dir": "mips", "grammar": "mips"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.id, "mips");
+        assert_eq!(manifest.languages.len(), 1);
+        assert_eq!(manifest.languages[0].grammar, "mips");
+    }
+
+    #[test]
+    fn manifest_languages_default_to_empty() {
+        let manifest: ExtensionManifest =
+            serde_json::from_str(r#"{"id": "themes-only", "name": "Themes Only", "version": "0.1.0"}"#).unwrap();
+        assert!(manifest.languages.is_empty());
+    }
+
+    /// A scratch directory under the OS temp dir, unique to the calling
+    /// test, removed on drop so tests don't leak files into each other.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("disass-gpui-test-{name}-{:?}", std::thread::current().id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn copy_dir_recursive_mirrors_nested_structure() {
+        let tmp = TempDir::new("copy-dir-recursive");
+        let src = tmp.0.join("src");
+        let dst = tmp.0.join("dst");
+
+        fs::create_dir_all(src.join("languages/mips")).unwrap();
+        fs::write(src.join("manifest.json"), "{}").unwrap();
+        fs::write(src.join("languages/mips/config.toml"), "name = \"MIPS\"").unwrap();
+
+        copy_dir_recursive(&src, &dst).unwrap();
+
+        assert_eq!(fs::read_to_string(dst.join("manifest.json")).unwrap(), "{}");
+        assert_eq!(
+            fs::read_to_string(dst.join("languages/mips/config.toml")).unwrap(),
+            "name = \"MIPS\""
+        );
+    }
+}
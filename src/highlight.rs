@@ -0,0 +1,135 @@
+//! Resolves tree-sitter capture names to theme styles through a small
+//! indirection table, so a theme switch only rebuilds this table instead of
+//! re-parsing or re-walking the syntax tree.
+
+use std::sync::Arc;
+
+use crate::theme::{Color, Theme};
+
+/// An index into a theme's ordered list of named highlight styles.
+///
+/// `DEFAULT` is the sentinel used when a capture has no matching entry in
+/// the active theme (e.g. the grammar emits `operator` but the theme only
+/// styles `keyword`), falling back to the theme's default text color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightId(u32);
+
+impl HighlightId {
+    pub const DEFAULT: HighlightId = HighlightId(u32::MAX);
+
+    pub(crate) fn new(ix: u32) -> Self {
+        HighlightId(ix)
+    }
+
+    /// Resolves this id to a concrete style against `theme`.
+    pub fn style(self, theme: &Theme) -> HighlightStyle {
+        if self == Self::DEFAULT {
+            return HighlightStyle::default_for(theme);
+        }
+        theme
+            .highlights
+            .get(self.0 as usize)
+            .map(|(_, style)| HighlightStyle {
+                color: style.color,
+                bold: style.bold,
+                italic: style.italic,
+                underline: style.underline,
+            })
+            .unwrap_or_else(|| HighlightStyle::default_for(theme))
+    }
+}
+
+/// Color plus the font attributes a syntax style can carry, beyond what the
+/// old hardcoded `match` on capture name supported.
+#[derive(Debug, Clone, Copy)]
+pub struct HighlightStyle {
+    pub color: Color,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl HighlightStyle {
+    fn default_for(theme: &Theme) -> Self {
+        HighlightStyle { color: theme.text, bold: false, italic: false, underline: false }
+    }
+}
+
+/// Maps a grammar's highlight-query capture indices to `HighlightId`s for
+/// the currently active theme.
+///
+/// Built once per (language, theme) pair by aligning `capture_names` (the
+/// query's capture list, in query order) against the theme's styled
+/// captures, using longest dotted-prefix matching so e.g. `keyword.control`
+/// falls back to a theme that only defines `keyword`.
+#[derive(Clone)]
+pub struct HighlightMap(Arc<[HighlightId]>);
+
+impl HighlightMap {
+    pub fn new(capture_names: &[&str], theme: &Theme) -> Self {
+        HighlightMap(capture_names.iter().map(|name| theme.best_match(name)).collect())
+    }
+
+    /// Looks up the `HighlightId` for a capture by its index in the query
+    /// this map was built from.
+    pub fn get(&self, capture_ix: u32) -> HighlightId {
+        self.0.get(capture_ix as usize).copied().unwrap_or(HighlightId::DEFAULT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::{Appearance, SyntaxStyle};
+    use std::collections::HashMap;
+
+    fn test_theme() -> Theme {
+        let styled = SyntaxStyle {
+            color: Color { r: 0x10, g: 0x20, b: 0x30, a: 0xff },
+            bold: true,
+            italic: false,
+            underline: true,
+        };
+        Theme {
+            name: "test".to_string(),
+            appearance: Appearance::Dark,
+            background: Color { r: 0, g: 0, b: 0, a: 0xff },
+            text: Color { r: 0xff, g: 0xff, b: 0xff, a: 0xff },
+            syntax: HashMap::new(),
+            highlights: vec![("keyword".to_string(), styled)],
+        }
+    }
+
+    #[test]
+    fn style_resolves_matched_id_from_theme() {
+        let theme = test_theme();
+        let style = HighlightId::new(0).style(&theme);
+        assert_eq!(style.color, theme.highlights[0].1.color);
+        assert!(style.bold);
+        assert!(style.underline);
+    }
+
+    #[test]
+    fn style_falls_back_to_default_for_out_of_range_id() {
+        let theme = test_theme();
+        let style = HighlightId::new(99).style(&theme);
+        assert_eq!(style.color, theme.text);
+        assert!(!style.bold && !style.italic && !style.underline);
+    }
+
+    #[test]
+    fn style_falls_back_to_default_for_default_id() {
+        let theme = test_theme();
+        let style = HighlightId::DEFAULT.style(&theme);
+        assert_eq!(style.color, theme.text);
+    }
+
+    #[test]
+    fn highlight_map_resolves_exact_and_missing_captures() {
+        let theme = test_theme();
+        let map = HighlightMap::new(&["keyword", "operator"], &theme);
+        assert_eq!(map.get(0), theme.best_match("keyword"));
+        assert_eq!(map.get(1), HighlightId::DEFAULT);
+        assert_eq!(map.get(2), HighlightId::DEFAULT); // out of range
+    }
+}
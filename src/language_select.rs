@@ -0,0 +1,182 @@
+//! Matcher-based language selection: picks the right registered language
+//! for an opened file by testing each `LanguageConfig.matcher` against the
+//! file's path suffix or first line, instead of assuming "asm" is the only
+//! language the user could ever open.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use languages::{Language, LanguageConfig, LanguageRegistry, LoadedLanguage};
+
+/// Every `LanguageConfig` registered so far — by the bundled ASM extension
+/// or any installed extension — so a file can be matched to the right
+/// language without the caller knowing its name up front.
+///
+/// `register_language` is the *only* way to add a language here: it
+/// registers with `LanguageRegistry` and records the matcher in the same
+/// call, so there's a single source of truth instead of two call sites
+/// (one per store) that a future language could register with one and
+/// forget the other.
+#[derive(Default)]
+pub struct LanguageSelector {
+    configs: Mutex<Vec<LanguageConfig>>,
+}
+
+impl LanguageSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a language with both `registry` and this selector in one
+    /// call. `load` builds the `LoadedLanguage` on demand; it never needs
+    /// to fail, so unlike `LanguageRegistry::register_language`'s raw
+    /// callback, there's no `Result` to construct at the call site.
+    pub fn register_language(
+        &self,
+        registry: &LanguageRegistry,
+        config: LanguageConfig,
+        load: impl Fn() -> LoadedLanguage + Send + Sync + 'static,
+    ) {
+        registry.register_language(
+            config.name.clone(),
+            config.grammar.clone(),
+            config.matcher.clone(),
+            config.hidden,
+            Arc::new(move || Ok(load())),
+        );
+        self.configs.lock().unwrap().push(config);
+    }
+
+    /// Resolves a grammar id (e.g. `"asm"`, from `Isa::grammar_id`) to the
+    /// language registered for it, if any. Unlike looking a language up by
+    /// its human-facing name, a grammar id is guaranteed to match whatever a
+    /// bundled or installed extension actually registered, since both sides
+    /// read it from the same `config.toml` field.
+    pub fn language_for_grammar(&self, grammar: &str, registry: &LanguageRegistry) -> Option<Arc<Language>> {
+        let name = self
+            .configs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|config| config.grammar == grammar)
+            .map(|config| config.name.clone())?;
+        registry.language(&name)
+    }
+
+    /// Resolves `path` to a loaded language by matching registered
+    /// matchers' path suffixes against it.
+    pub fn language_for_path(&self, path: &Path, registry: &LanguageRegistry) -> Option<Arc<Language>> {
+        let name = self.name_for_path(path)?;
+        registry.language(&name)
+    }
+
+    /// Resolves a buffer's first line to a loaded language, for input (like
+    /// an objdump dump) that isn't backed by a file with a suffix.
+    pub fn language_for_first_line(&self, text: &str, registry: &LanguageRegistry) -> Option<Arc<Language>> {
+        let first_line = text.lines().next().unwrap_or("");
+        let name = self.name_for_first_line(first_line)?;
+        registry.language(&name)
+    }
+
+    fn name_for_path(&self, path: &Path) -> Option<String> {
+        let suffix = path.extension()?.to_str()?;
+        let configs = self.configs.lock().unwrap();
+        configs
+            .iter()
+            .find(|config| config.matcher.path_suffixes.iter().any(|s| s == suffix))
+            .map(|config| config.name.clone())
+    }
+
+    fn name_for_first_line(&self, first_line: &str) -> Option<String> {
+        let configs = self.configs.lock().unwrap();
+        configs
+            .iter()
+            .find(|config| {
+                config
+                    .matcher
+                    .first_line_pattern
+                    .as_ref()
+                    .is_some_and(|pattern| pattern.is_match(first_line))
+            })
+            .map(|config| config.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asm_config() -> LanguageConfig {
+        toml::from_str(
+            r#"
+            name = "Assembly"
+            grammar = "asm"
+            hidden = false
+
+            [matcher]
+            path_suffixes = ["asm", "s"]
+            first_line_pattern = "^\\s*\\.(globl|section|text)\\b"
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn mips_config() -> LanguageConfig {
+        toml::from_str(
+            r#"
+            name = "MIPS Assembly"
+            grammar = "mips"
+            hidden = false
+
+            [matcher]
+            path_suffixes = ["mips"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn selector_with(configs: Vec<LanguageConfig>) -> LanguageSelector {
+        let selector = LanguageSelector::new();
+        *selector.configs.lock().unwrap() = configs;
+        selector
+    }
+
+    #[test]
+    fn name_for_path_matches_registered_suffix() {
+        let selector = selector_with(vec![asm_config(), mips_config()]);
+        assert_eq!(selector.name_for_path(Path::new("listing.mips")), Some("MIPS Assembly".to_string()));
+        assert_eq!(selector.name_for_path(Path::new("listing.s")), Some("Assembly".to_string()));
+    }
+
+    #[test]
+    fn name_for_path_returns_none_for_unmatched_suffix() {
+        let selector = selector_with(vec![asm_config()]);
+        assert_eq!(selector.name_for_path(Path::new("listing.rs")), None);
+    }
+
+    #[test]
+    fn name_for_path_returns_none_without_extension() {
+        let selector = selector_with(vec![asm_config()]);
+        assert_eq!(selector.name_for_path(Path::new("listing")), None);
+    }
+
+    #[test]
+    fn name_for_first_line_matches_pattern() {
+        let selector = selector_with(vec![asm_config()]);
+        assert_eq!(selector.name_for_first_line(".globl _main"), Some("Assembly".to_string()));
+    }
+
+    #[test]
+    fn name_for_first_line_returns_none_without_a_pattern() {
+        // `mips_config` has no `first_line_pattern`, so it should never match
+        // on first-line content alone, only by suffix.
+        let selector = selector_with(vec![mips_config()]);
+        assert_eq!(selector.name_for_first_line(".globl _main"), None);
+    }
+
+    #[test]
+    fn name_for_first_line_returns_none_for_unmatched_text() {
+        let selector = selector_with(vec![asm_config()]);
+        assert_eq!(selector.name_for_first_line("not assembly at all"), None);
+    }
+}
@@ -3,8 +3,23 @@ use gpui::prelude::*;
 use languages::{LanguageRegistry, LanguageConfig, LanguageQueries, LoadedLanguage};
 use tree_sitter_asm;
 
+mod disasm;
+mod extensions;
+mod highlight;
+mod language_select;
+mod theme;
+mod theme_watch;
+
+use extensions::ExtensionStore;
+
+use highlight::HighlightMap;
+use language_select::LanguageSelector;
+use std::collections::HashMap;
+use std::path::Path;
+use theme::Theme;
+
 /// Embeds and registers the Zed ASM extension into the language registry.
-fn register_asm_extension(registry: &LanguageRegistry) {
+fn register_asm_extension(registry: &LanguageRegistry, selector: &LanguageSelector) {
     // 1. Register the native Tree-sitter grammar for ASM
     registry.register_native_grammars([
         ("asm", tree_sitter_asm::language()),
@@ -23,24 +38,19 @@ fn register_asm_extension(registry: &LanguageRegistry) {
     ));
     // (Add injections.scm or locals.scm if the extension provides them)
 
-    // 4. Register the language with the registry
-    registry.register_language(
-        config.name.clone(),      // e.g. "Assembly"
-        config.grammar.clone(),   // "asm"
-        config.matcher.clone(),   // file suffix / first-line matcher
-        config.hidden,
-        Arc::new(move || {
-            Ok(LoadedLanguage {
-                config: config.clone(),
-                queries: queries.clone(),
-                context_provider: None,
-                toolchain_provider: None,
-            })
-        }),
-    );
+    // 4. Register the language with the registry and the selector together,
+    // so the two can't drift out of sync.
+    let loaded_config = config.clone();
+    selector.register_language(registry, config, move || LoadedLanguage {
+        config: loaded_config.clone(),
+        queries: queries.clone(),
+        context_provider: None,
+        toolchain_provider: None,
+    });
 }
 
-/// Hardcoded ARM64 assembly for the demo; swap this out for dynamic input.
+/// Fallback demo text, shown when no object file is given on the command
+/// line or it fails to disassemble.
 const ARM64_CODE: &str = r#"
     .globl _main                  // global entry
 _main:
@@ -52,45 +62,73 @@ _main:
     ret
 "#;
 
-/// Maps common highlight capture names to RGB theme colors.
-struct ThemeColors {
-    background: u32,
-    default_text: u32,
-    keyword: u32,
-    comment: u32,
-    register: u32,
-    number: u32,
-    label: u32,
-}
-impl ThemeColors {
-    fn dark_theme() -> Self {
-        Self {
-            background: 0x1e1e1e,
-            default_text: 0xd4d4d4,
-            keyword: 0x569cd6,
-            comment: 0x6a9955,
-            register: 0x4ec9b0,
-            number: 0xb5cea8,
-            label: 0xdcdcaa,
-        }
-    }
-}
-
 /// The main application state, holds highlighted lines.
 struct AssemblyViewer {
     lines: Vec<Vec<zed_syntax::HighlightSegment>>,
-    theme: ThemeColors,
+    /// The grammar's highlight-query capture names, in query order; fixed
+    /// for the lifetime of the viewer since they come from the language,
+    /// not the theme.
+    capture_names: Vec<String>,
+    /// `capture_names`, indexed by name, so a segment's capture string can
+    /// be resolved to its query index without a linear scan per segment.
+    capture_index: HashMap<String, u32>,
+    /// Rebuilt whenever the theme changes; everything else here survives a
+    /// theme switch untouched.
+    highlight_map: HighlightMap,
+    theme: Arc<Theme>,
+    /// Function symbols found while disassembling, if the listing came
+    /// from a real binary rather than the hardcoded demo. Exposed via
+    /// `symbols()` so a future navigation sidebar can jump `uniform_list` to
+    /// one of these by address.
+    symbols: Vec<disasm::Symbol>,
 }
 
 impl AssemblyViewer {
-    fn new(source: &str, theme: ThemeColors, registry: &LanguageRegistry) -> Self {
-        // Lookup the ASM language and highlight the source text
-        let asm_lang = registry
-            .language("asm")
-            .expect("ASM language not found in registry");
-        let lines = asm_lang.highlight(source);
-
-        AssemblyViewer { lines, theme }
+    /// Opens `source`, selecting its language via `selector` — from `path`'s
+    /// suffix if given, else the text's first line — with `override_grammar`
+    /// (a grammar id, e.g. `Isa::grammar_id()`) taking priority over either
+    /// when the caller already knows which grammar decoded `source`.
+    fn open(
+        source: &str,
+        path: Option<&Path>,
+        override_grammar: Option<&str>,
+        symbols: Vec<disasm::Symbol>,
+        theme: Arc<Theme>,
+        registry: &LanguageRegistry,
+        selector: &LanguageSelector,
+    ) -> Self {
+        let language = override_grammar
+            .and_then(|grammar| selector.language_for_grammar(grammar, registry))
+            .or_else(|| path.and_then(|path| selector.language_for_path(path, registry)))
+            .or_else(|| selector.language_for_first_line(source, registry))
+            .expect("no registered language matches this file");
+
+        let lines = language.highlight(source);
+        let capture_names = language.capture_names();
+
+        let capture_index = capture_names
+            .iter()
+            .enumerate()
+            .map(|(ix, name)| (name.clone(), ix as u32))
+            .collect();
+        let refs: Vec<&str> = capture_names.iter().map(String::as_str).collect();
+        let highlight_map = HighlightMap::new(&refs, &theme);
+
+        AssemblyViewer { lines, capture_names, capture_index, highlight_map, theme, symbols }
+    }
+
+    /// Rebuilds `highlight_map` for a new theme without touching the parsed
+    /// `lines`, so a theme switch is cheap relative to re-parsing.
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        let refs: Vec<&str> = self.capture_names.iter().map(String::as_str).collect();
+        self.highlight_map = HighlightMap::new(&refs, &theme);
+        self.theme = theme;
+    }
+
+    /// Function symbols found while disassembling, in address order, for a
+    /// navigation sidebar to jump `uniform_list` to.
+    fn symbols(&self) -> &[disasm::Symbol] {
+        &self.symbols
     }
 }
 
@@ -98,8 +136,8 @@ impl Render for AssemblyViewer {
     fn render(&mut self, _win: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         div()
             .size_full()
-            .bg(rgb(self.theme.background))
-            .text_color(rgb(self.theme.default_text))
+            .bg(rgb(self.theme.background.to_rgb_u32()))
+            .text_color(rgb(self.theme.text.to_rgb_u32()))
             .child(
                 gpui::uniform_list(
                     cx.entity().clone(),
@@ -110,17 +148,25 @@ impl Render for AssemblyViewer {
                         for i in range {
                             let mut line = div().flex();
                             for seg in &viewer.lines[i] {
-                                let color = match seg.capture.as_str() {
-                                    "label"    => viewer.theme.label,
-                                    "keyword"  => viewer.theme.keyword,
-                                    "register" => viewer.theme.register,
-                                    "number"   => viewer.theme.number,
-                                    "comment"  => viewer.theme.comment,
-                                    _           => viewer.theme.default_text,
-                                };
-                                line = line.child(
-                                    div().text_color(rgb(color)).child(seg.text.as_str()),
-                                );
+                                let capture_ix = viewer
+                                    .capture_index
+                                    .get(seg.capture.as_str())
+                                    .copied()
+                                    .unwrap_or(u32::MAX);
+                                let style = viewer.highlight_map.get(capture_ix).style(&viewer.theme);
+                                let mut text = div()
+                                    .text_color(rgb(style.color.to_rgb_u32()))
+                                    .child(seg.text.as_str());
+                                if style.bold {
+                                    text = text.font_weight(gpui::FontWeight::BOLD);
+                                }
+                                if style.italic {
+                                    text = text.italic();
+                                }
+                                if style.underline {
+                                    text = text.underline();
+                                }
+                                line = line.child(text);
                             }
                             items.push(line);
                         }
@@ -132,22 +178,108 @@ impl Render for AssemblyViewer {
     }
 }
 
+/// Where user theme-family files live, e.g. `~/.config/disass-gpui/themes`.
+fn themes_dir() -> std::path::PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("disass-gpui")
+        .join("themes")
+}
+
+/// Where installed extensions live, e.g.
+/// `~/.local/share/disass-gpui/extensions/installed`.
+fn extensions_dir() -> std::path::PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("disass-gpui")
+        .join("extensions")
+        .join("installed")
+}
+
 fn main() {
     Application::new().run(|cx: &mut App| {
         // Create and configure the language registry
         let languages = Arc::new(LanguageRegistry::new(cx.background_executor()));
+        let selector = Arc::new(LanguageSelector::new());
         // Ensure the registry has our ASM extension
-        register_asm_extension(&languages);
+        register_asm_extension(&languages, &selector);
         // Initialize the language registry with the application context
         languages.init(cx);
+
+        // Register the bundled default, then load user themes on top of it.
+        // The viewer always starts with the theme named "Dark Default"; a
+        // user file only changes it by defining a theme of that name.
+        let themes = Arc::new(theme::ThemeRegistry::new(themes_dir()));
+        themes
+            .register_bundled(include_str!("../assets/themes/default.json"))
+            .expect("bundled default theme is malformed");
+        if let Err(err) = themes.load_all() {
+            eprintln!("failed to load user themes: {err}");
+        }
+        let active_theme = themes
+            .get("Dark Default")
+            .expect("bundled default theme failed to register");
+
+        // Load any extensions (grammars, languages, themes) the user has
+        // installed, turning the viewer into a multi-ISA host.
+        let extension_store = Arc::new(ExtensionStore::new(
+            extensions_dir(),
+            languages.clone(),
+            themes.clone(),
+            selector.clone(),
+        ));
+        if let Err(err) = extension_store.load_installed() {
+            eprintln!("failed to scan installed extensions: {err}");
+        }
+
+        // Disassemble the binary given on the command line, if any, falling
+        // back to the hardcoded ARM64 demo when none is given or it fails
+        // to disassemble.
+        let binary_path = std::env::args().nth(1);
+        let listing = binary_path.as_deref().and_then(|path| {
+            match disasm::disassemble_file(Path::new(path)) {
+                Ok(listing) => Some(listing),
+                Err(err) => {
+                    eprintln!("failed to disassemble {path}: {err}");
+                    None
+                }
+            }
+        });
+        let (source, override_grammar, symbols) = match listing {
+            Some(listing) => (listing.text, Some(listing.isa.grammar_id()), listing.symbols),
+            None => (ARM64_CODE.to_string(), None, Vec::new()),
+        };
+
         // Open the main window
         let bounds = Bounds::centered(None, size(px(800.0), px(600.0)), cx);
-        cx.open_window(
-            WindowOptions { window_bounds: Some(WindowBounds::Windowed(bounds)), ..Default::default() },
-            move |_, _window_cx| {
-                cx.new(|_model_cx| AssemblyViewer::new(ARM64_CODE, ThemeColors::dark_theme(), &languages))
-            },
-        )
-        .unwrap();
+        let window = cx
+            .open_window(
+                WindowOptions { window_bounds: Some(WindowBounds::Windowed(bounds)), ..Default::default() },
+                move |_, _window_cx| {
+                    cx.new(|_model_cx| {
+                        AssemblyViewer::open(
+                            &source,
+                            Some(Path::new("listing.asm")),
+                            override_grammar,
+                            symbols,
+                            active_theme,
+                            &languages,
+                            &selector,
+                        )
+                    })
+                },
+            )
+            .unwrap();
+
+        // Re-theme the viewer live whenever its active theme's file changes
+        // on disk.
+        if let Ok(viewer) = window.root(cx) {
+            theme_watch::watch_theme(themes_dir(), "Dark Default".to_string(), themes, cx, move |theme, async_cx| {
+                let _ = viewer.update(async_cx, |viewer, cx| {
+                    viewer.set_theme(theme);
+                    cx.notify();
+                });
+            });
+        }
     });
 }
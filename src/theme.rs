@@ -0,0 +1,370 @@
+//! User-loadable themes: parsing theme-family JSON files and caching the
+//! themes they define so the viewer can be recolored without a recompile.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Deserializer};
+
+/// An RGBA color parsed from a `#RRGGBB` or `#RRGGBBAA` hex literal.
+///
+/// Theme files are authored by hand, so we accept the shorthand form
+/// (implicit full alpha) in addition to the explicit one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    /// Packs the color into the `0xRRGGBB` form `gpui::rgb` expects.
+    pub fn to_rgb_u32(self) -> u32 {
+        ((self.r as u32) << 16) | ((self.g as u32) << 8) | self.b as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorParseError(String);
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected \"#RRGGBB[AA]\", got {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
+impl std::str::FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').ok_or_else(|| ColorParseError(s.to_string()))?;
+        let bad = || ColorParseError(s.to_string());
+
+        let (rgb, a) = match hex.len() {
+            6 => (hex, 0xff),
+            8 => {
+                let a = u8::from_str_radix(&hex[6..8], 16).map_err(|_| bad())?;
+                (&hex[..6], a)
+            }
+            _ => return Err(bad()),
+        };
+
+        let r = u8::from_str_radix(&rgb[0..2], 16).map_err(|_| bad())?;
+        let g = u8::from_str_radix(&rgb[2..4], 16).map_err(|_| bad())?;
+        let b = u8::from_str_radix(&rgb[4..6], 16).map_err(|_| bad())?;
+
+        Ok(Color { r, g, b, a })
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// `"dark"` or `"light"`, matching the `appearance` field of a theme entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Appearance {
+    Dark,
+    Light,
+}
+
+/// The color assignments for one named tree-sitter capture, e.g. `keyword`
+/// or `keyword.control`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyntaxStyle {
+    pub color: Color,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub underline: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeStyle {
+    pub background: Color,
+    pub text: Color,
+    #[serde(default)]
+    pub syntax: HashMap<String, SyntaxStyle>,
+}
+
+/// One theme within a theme-family file, e.g. "One Dark".
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeConfig {
+    pub name: String,
+    pub appearance: Appearance,
+    pub style: ThemeStyle,
+}
+
+/// A theme-family file as authored on disk: `{ name, author, themes: [...] }`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeFamily {
+    pub name: String,
+    pub author: String,
+    pub themes: Vec<ThemeConfig>,
+}
+
+/// A single resolved theme, ready to be consulted by the viewer.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub appearance: Appearance,
+    pub background: Color,
+    pub text: Color,
+    pub syntax: HashMap<String, SyntaxStyle>,
+    /// `syntax`, flattened to a stable order so a `HighlightId` (an index
+    /// into this list) stays valid for as long as the theme does.
+    pub highlights: Vec<(String, SyntaxStyle)>,
+}
+
+impl From<ThemeConfig> for Theme {
+    fn from(config: ThemeConfig) -> Self {
+        let mut highlights: Vec<_> = config
+            .style
+            .syntax
+            .iter()
+            .map(|(name, style)| (name.clone(), style.clone()))
+            .collect();
+        highlights.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Theme {
+            name: config.name,
+            appearance: config.appearance,
+            background: config.style.background,
+            text: config.style.text,
+            syntax: config.style.syntax,
+            highlights,
+        }
+    }
+}
+
+impl Theme {
+    /// Resolves a tree-sitter capture name (e.g. `keyword.control`) to a
+    /// `HighlightId` by trying the full name, then progressively shorter
+    /// dotted prefixes, falling back to `HighlightId::DEFAULT` if nothing
+    /// in this theme matches.
+    pub fn best_match(&self, capture: &str) -> crate::highlight::HighlightId {
+        let mut candidate = capture;
+        loop {
+            if let Some(ix) = self.highlights.iter().position(|(name, _)| name == candidate) {
+                return crate::highlight::HighlightId::new(ix as u32);
+            }
+            match candidate.rfind('.') {
+                Some(dot) => candidate = &candidate[..dot],
+                None => return crate::highlight::HighlightId::DEFAULT,
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(std::io::Error),
+    Parse { path: PathBuf, source: serde_json::Error },
+    NotFound(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(err) => write!(f, "{err}"),
+            ThemeError::Parse { path, source } => {
+                write!(f, "failed to parse theme file {}: {source}", path.display())
+            }
+            ThemeError::NotFound(name) => write!(f, "no theme named {name:?} is installed"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl From<std::io::Error> for ThemeError {
+    fn from(err: std::io::Error) -> Self {
+        ThemeError::Io(err)
+    }
+}
+
+/// Loads theme-family files from a config directory and caches the themes
+/// they define by name.
+///
+/// Parsed themes are cached in `themes` so repeated lookups (e.g. once per
+/// frame while a theme picker is open) don't re-read and re-parse disk
+/// files. `get` never reads disk: `clear` drops a cached entry and a
+/// following `load_dir` repopulates it, which is how hot-reload invalidates
+/// a theme after its file changes on disk.
+pub struct ThemeRegistry {
+    themes_dir: PathBuf,
+    themes: Mutex<HashMap<String, Arc<Theme>>>,
+}
+
+impl ThemeRegistry {
+    pub fn new(themes_dir: impl Into<PathBuf>) -> Self {
+        Self { themes_dir: themes_dir.into(), themes: Mutex::new(HashMap::new()) }
+    }
+
+    /// Loads every `*.json` theme-family file in the themes directory,
+    /// populating the cache. Called once at startup.
+    pub fn load_all(&self) -> Result<(), ThemeError> {
+        let themes_dir = self.themes_dir.clone();
+        self.load_dir(&themes_dir)
+    }
+
+    /// Loads every `*.json` theme-family file in an arbitrary directory,
+    /// merging the themes it defines into the cache. Used both for the
+    /// user's own `themes_dir` and for themes bundled inside an installed
+    /// extension.
+    pub fn load_dir(&self, dir: &Path) -> Result<(), ThemeError> {
+        let mut themes = self.themes.lock().unwrap();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            for theme in Self::parse_family(&path)? {
+                themes.insert(theme.name.clone(), Arc::new(theme));
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_family(path: &Path) -> Result<Vec<Theme>, ThemeError> {
+        let contents = fs::read_to_string(path)?;
+        let family: ThemeFamily = serde_json::from_str(&contents)
+            .map_err(|source| ThemeError::Parse { path: path.to_path_buf(), source })?;
+        Ok(family.themes.into_iter().map(Theme::from).collect())
+    }
+
+    /// Whether any `*.json` theme-family file in `dir` defines a theme named
+    /// `name`, without touching the cache. Used before a reload to tell a
+    /// file-backed theme (which `load_dir` can repopulate) apart from a
+    /// bundled one like the default theme (which `load_dir` never sees and
+    /// would otherwise be cleared and never come back).
+    pub fn dir_defines(dir: &Path, name: &str) -> Result<bool, ThemeError> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if Self::parse_family(&path)?.iter().any(|theme| theme.name == name) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Parses a theme-family JSON string and inserts the themes it defines
+    /// into the cache. Used to seed a bundled default theme that ships with
+    /// the app, independent of whatever is on disk in `themes_dir`.
+    pub fn register_bundled(&self, family_json: &str) -> Result<(), ThemeError> {
+        let family: ThemeFamily = serde_json::from_str(family_json)
+            .map_err(|source| ThemeError::Parse { path: PathBuf::from("<bundled>"), source })?;
+        let mut themes = self.themes.lock().unwrap();
+        for theme in family.themes.into_iter().map(Theme::from) {
+            themes.insert(theme.name.clone(), Arc::new(theme));
+        }
+        Ok(())
+    }
+
+    /// Lists the names of every theme currently cached.
+    pub fn list(&self) -> Vec<String> {
+        let themes = self.themes.lock().unwrap();
+        let mut names: Vec<_> = themes.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns a cached theme by name, if one is loaded.
+    pub fn get(&self, name: &str) -> Result<Arc<Theme>, ThemeError> {
+        self.themes
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ThemeError::NotFound(name.to_string()))
+    }
+
+    /// Drops a cached theme so the next `get` (after a reload) picks up
+    /// fresh contents from disk.
+    pub fn clear(&self, name: &str) {
+        self.themes.lock().unwrap().remove(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_parses_six_digit_hex() {
+        let color: Color = "#1e90ff".parse().unwrap();
+        assert_eq!(color, Color { r: 0x1e, g: 0x90, b: 0xff, a: 0xff });
+    }
+
+    #[test]
+    fn color_parses_eight_digit_hex() {
+        let color: Color = "#1e90ff80".parse().unwrap();
+        assert_eq!(color, Color { r: 0x1e, g: 0x90, b: 0xff, a: 0x80 });
+    }
+
+    #[test]
+    fn color_rejects_missing_hash() {
+        let err = "1e90ff".parse::<Color>().unwrap_err();
+        assert_eq!(err.to_string(), r##"expected "#RRGGBB[AA]", got "1e90ff""##);
+    }
+
+    #[test]
+    fn color_rejects_wrong_length() {
+        assert!("#1e90f".parse::<Color>().is_err());
+        assert!("#1e90ff8".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn color_rejects_non_hex_digits() {
+        assert!("#zzzzzz".parse::<Color>().is_err());
+    }
+
+    fn theme_with_highlights(names: &[&str]) -> Theme {
+        let style = SyntaxStyle { color: Color { r: 0, g: 0, b: 0, a: 0xff }, bold: false, italic: false, underline: false };
+        Theme {
+            name: "test".to_string(),
+            appearance: Appearance::Dark,
+            background: style.color,
+            text: style.color,
+            syntax: HashMap::new(),
+            highlights: names.iter().map(|name| (name.to_string(), style.clone())).collect(),
+        }
+    }
+
+    #[test]
+    fn best_match_finds_exact_capture() {
+        let theme = theme_with_highlights(&["keyword", "keyword.control"]);
+        assert_eq!(theme.best_match("keyword.control"), crate::highlight::HighlightId::new(1));
+        assert_ne!(theme.best_match("keyword.control"), crate::highlight::HighlightId::DEFAULT);
+    }
+
+    #[test]
+    fn best_match_falls_back_to_dotted_prefix() {
+        let theme = theme_with_highlights(&["keyword"]);
+        assert_eq!(theme.best_match("keyword.control"), theme.best_match("keyword"));
+    }
+
+    #[test]
+    fn best_match_falls_back_to_default() {
+        let theme = theme_with_highlights(&["keyword"]);
+        assert_eq!(theme.best_match("operator"), crate::highlight::HighlightId::DEFAULT);
+    }
+}
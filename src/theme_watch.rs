@@ -0,0 +1,117 @@
+//! Watches the themes directory for changes and hot-reloads the affected
+//! theme, so someone iterating on a color scheme sees their edits land in
+//! the viewer without restarting the app.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use gpui::{App, AsyncApp};
+use notify::{RecursiveMode, Watcher};
+
+use crate::theme::{Theme, ThemeRegistry};
+
+/// Watches `themes_dir` on a dedicated OS thread and, on every relevant
+/// filesystem event, clears `theme_name` from `themes` and reloads the
+/// whole directory — but only if `theme_name` is actually defined by a file
+/// in `themes_dir`, so a bundled theme that only lives in the registry's
+/// cache is never cleared with no file to repopulate it from — then invokes
+/// `on_reload` with the refreshed theme on the app's foreground executor so
+/// callers can update GPUI entities.
+///
+/// The watcher and its `recv()` loop are blocking by nature, so they run on
+/// a plain `std::thread`, never on a GPUI executor — a blocking wait there
+/// would otherwise stall the foreground executor until the first event
+/// arrived. Reloaded themes are handed to the foreground side through an
+/// async channel, so the only per-event foreground work is `on_reload`
+/// itself.
+///
+/// Watching the whole directory rather than one file keeps this simple: a
+/// theme-family file can define several themes, and editors often write a
+/// new file and rename it over the old one rather than editing in place.
+pub fn watch_theme(
+    themes_dir: PathBuf,
+    theme_name: String,
+    themes: Arc<ThemeRegistry>,
+    cx: &App,
+    mut on_reload: impl FnMut(Arc<Theme>, &mut AsyncApp) + Send + 'static,
+) {
+    let (theme_tx, mut theme_rx) = mpsc::unbounded();
+
+    std::thread::spawn(move || {
+        let (fs_tx, fs_rx) = std::sync::mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                let _ = fs_tx.send(event);
+            }
+        }) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                eprintln!("failed to start theme watcher: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = watcher.watch(&themes_dir, RecursiveMode::NonRecursive) {
+            eprintln!("failed to watch themes directory {}: {err}", themes_dir.display());
+            return;
+        }
+
+        // Blocks this dedicated thread while waiting for filesystem events;
+        // the GPUI foreground executor never touches this loop.
+        while let Ok(event) = fs_rx.recv() {
+            if !is_relevant(&event) {
+                continue;
+            }
+
+            // The active theme may be bundled (e.g. the default theme,
+            // registered once via `register_bundled` and never written to
+            // `themes_dir`). `load_dir` only ever reads `themes_dir`, so
+            // clearing a bundled theme here would drop it from the cache
+            // for good the next time any unrelated file in the directory
+            // changes. Only clear and reload when `themes_dir` itself
+            // actually defines it.
+            match ThemeRegistry::dir_defines(&themes_dir, &theme_name) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(err) => {
+                    eprintln!("failed to check themes directory {}: {err}", themes_dir.display());
+                    continue;
+                }
+            }
+
+            themes.clear(&theme_name);
+            if let Err(err) = themes.load_dir(&themes_dir) {
+                eprintln!("failed to reload themes from {}: {err}", themes_dir.display());
+                continue;
+            }
+
+            match themes.get(&theme_name) {
+                Ok(theme) => {
+                    if theme_tx.unbounded_send(theme).is_err() {
+                        break; // foreground task is gone; stop watching
+                    }
+                }
+                Err(err) => eprintln!("theme {theme_name:?} missing after reload: {err}"),
+            }
+        }
+
+        // Keeps the watcher (and its subscription) alive for the lifetime
+        // of this thread.
+        drop(watcher);
+    });
+
+    cx.spawn(move |mut async_cx| async move {
+        while let Some(theme) = theme_rx.next().await {
+            on_reload(theme, &mut async_cx);
+        }
+    })
+    .detach();
+}
+
+fn is_relevant(event: &notify::Event) -> bool {
+    use notify::EventKind::*;
+    matches!(event.kind, Create(_) | Modify(_) | Remove(_))
+        && event.paths.iter().any(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+}